@@ -0,0 +1,59 @@
+use crate::{AreaCode, CoordinateSystem, FileFormat, PointFormat};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{fs, path::Path, path::PathBuf, str::FromStr};
+
+/// On-disk defaults for the most commonly repeated `download` flags.
+/// Fields are kept as strings so they're parsed with the exact same
+/// `FromStr` impls the CLI uses, so a bad value is rejected the same way
+/// whichever place it came from.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    pub point_format: Option<String>,
+    pub file_format: Option<String>,
+    pub area_code: Option<String>,
+    pub coordinate_system: Option<String>,
+    pub concurrency: Option<usize>,
+}
+
+impl Config {
+    pub fn point_format(&self) -> Result<Option<PointFormat>> {
+        parse_field(self.point_format.as_deref())
+    }
+
+    pub fn file_format(&self) -> Result<Option<FileFormat>> {
+        parse_field(self.file_format.as_deref())
+    }
+
+    pub fn area_code(&self) -> Result<Option<AreaCode>> {
+        parse_field(self.area_code.as_deref())
+    }
+
+    pub fn coordinate_system(&self) -> Result<Option<CoordinateSystem>> {
+        parse_field(self.coordinate_system.as_deref())
+    }
+}
+
+fn parse_field<T: FromStr<Err = String>>(value: Option<&str>) -> Result<Option<T>> {
+    value
+        .map(|value| T::from_str(value).map_err(anyhow::Error::msg))
+        .transpose()
+}
+
+/// The default config file location: `$XDG_CONFIG_HOME/gis-lidar-downloader/config.toml`,
+/// falling back to the platform config directory (e.g. `~/.config/...` on Linux).
+pub fn default_config_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "gis-lidar-downloader")
+        .map(|dirs| dirs.config_dir().join("config.toml"))
+}
+
+/// Loads `path`, or an empty [`Config`] if no file exists there yet.
+pub fn load(path: &Path) -> Result<Config> {
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+}
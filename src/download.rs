@@ -0,0 +1,174 @@
+use crate::Link;
+use anyhow::{Context, Result};
+use reqwest::{Client, StatusCode};
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Base delay for exponential backoff between retry attempts.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// Number of attempts made for a single link before giving up on it.
+const MAX_RETRIES: u32 = 5;
+
+/// The result of attempting to download a single [`Link`].
+#[derive(Debug)]
+pub enum DownloadOutcome {
+    /// Holds the final file size in bytes, for progress reporting.
+    Succeeded(u64),
+    Skipped,
+    Failed(anyhow::Error),
+}
+
+/// Tally of [`DownloadOutcome`]s across a batch, printed once a run finishes.
+#[derive(Debug, Default)]
+pub struct DownloadSummary {
+    pub succeeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub bytes: u64,
+}
+
+impl DownloadSummary {
+    pub fn record(&mut self, outcome: &DownloadOutcome) {
+        match outcome {
+            DownloadOutcome::Succeeded(bytes) => {
+                self.succeeded += 1;
+                self.bytes += bytes;
+            }
+            DownloadOutcome::Skipped => self.skipped += 1,
+            DownloadOutcome::Failed(_) => self.failed += 1,
+        }
+    }
+}
+
+impl fmt::Display for DownloadSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} succeeded ({}), {} skipped, {} failed",
+            self.succeeded,
+            format_bytes(self.bytes),
+            self.skipped,
+            self.failed
+        )
+    }
+}
+
+/// Formats a byte count as a human-readable size, e.g. `12.3 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// The final output path a [`Link`] is downloaded to, e.g. `output/510_74.laz`.
+pub fn dest_path(output: &Path, link: &Link) -> PathBuf {
+    output.join(format!(
+        "{}_{}.{}",
+        link.coordinate.x, link.coordinate.y, link.point_format
+    ))
+}
+
+/// Downloads a single link, retrying transient failures with exponential
+/// backoff and resuming any partially written `.part` file. Never panics:
+/// failures are reported through the returned [`DownloadOutcome`].
+pub async fn download_link(client: &Client, link: &Link<'_>, output: &Path) -> DownloadOutcome {
+    match try_download_link(client, link, output).await {
+        Ok(outcome) => outcome,
+        Err(error) => DownloadOutcome::Failed(error),
+    }
+}
+
+async fn try_download_link(
+    client: &Client,
+    link: &Link<'_>,
+    output: &Path,
+) -> Result<DownloadOutcome> {
+    let dest = dest_path(output, link);
+
+    if dest.exists() {
+        match fetch_content_length(client, &link.url).await {
+            Ok(Some(expected)) if fs::metadata(&dest)?.len() == expected => {
+                return Ok(DownloadOutcome::Skipped);
+            }
+            Ok(Some(_)) => {}
+            _ => return Ok(DownloadOutcome::Skipped),
+        }
+    }
+
+    let part = PathBuf::from(format!("{}.part", dest.display()));
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match attempt_download(client, link, &part).await {
+            Ok(()) => break,
+            Err(error) if attempt >= MAX_RETRIES => {
+                return Err(error).with_context(|| {
+                    format!("Giving up on {} after {} attempts", link.url, attempt)
+                });
+            }
+            Err(error) => {
+                let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                eprintln!(
+                    "Attempt {} for {} failed ({}), retrying in {:?}",
+                    attempt, link.url, error, delay
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+
+    fs::rename(&part, &dest).with_context(|| format!("Failed to finalize {}", dest.display()))?;
+    let size = fs::metadata(&dest)?.len();
+    Ok(DownloadOutcome::Succeeded(size))
+}
+
+async fn fetch_content_length(client: &Client, url: &str) -> Result<Option<u64>> {
+    let response = client.head(url).send().await?;
+    Ok(response.content_length())
+}
+
+async fn attempt_download(client: &Client, link: &Link<'_>, part: &Path) -> Result<()> {
+    let resume_from = fs::metadata(part)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+
+    // If a previous attempt wrote the whole file but was killed before the
+    // rename to its final path, the part file is already complete: issuing
+    // another Range request would ask for bytes past the end and get a 416.
+    if resume_from > 0 {
+        if let Some(expected) = fetch_content_length(client, &link.url).await? {
+            if resume_from == expected {
+                return Ok(());
+            }
+        }
+    }
+
+    let mut request = client.get(&link.url);
+    if resume_from > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_from));
+    }
+
+    let response = request.send().await?.error_for_status()?;
+    let resumed = resume_from > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+    let bytes = response.bytes().await?;
+
+    let mut file = if resumed {
+        OpenOptions::new().append(true).open(part)?
+    } else {
+        File::create(part)?
+    };
+    file.write_all(&bytes)?;
+
+    Ok(())
+}
@@ -1,18 +1,30 @@
-use clap::Parser;
-use futures::{stream, StreamExt};
+mod config;
+mod download;
+mod mosaic;
+mod projection;
+mod verify;
+
+use anyhow::Context;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use config::Config;
+use futures::{future, stream, StreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
+use projection::BoundingBox;
 use reqwest::Client;
 use std::{
-    fmt,
-    fs::{self, File},
-    io::Write,
-    path::Path,
+    fmt, fs,
+    path::{Path, PathBuf},
     str::FromStr,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, clap::ValueEnum)]
+#[allow(clippy::upper_case_acronyms)]
 enum PointFormat {
+    #[value(name = "gkot")]
     GKOT,
+    #[value(name = "otr")]
     OTR,
+    #[value(name = "dtm")]
     DTM,
 }
 
@@ -40,10 +52,14 @@ impl FromStr for PointFormat {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, clap::ValueEnum)]
+#[allow(clippy::upper_case_acronyms)]
 enum FileFormat {
+    #[value(name = "zlas")]
     ZLAS,
+    #[value(name = "laz")]
     LAZ,
+    #[value(name = "asc")]
     ASC,
 }
 
@@ -71,7 +87,7 @@ impl FromStr for FileFormat {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct AreaCode {
     letter: char,
     number: u32,
@@ -102,9 +118,11 @@ impl FromStr for AreaCode {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, clap::ValueEnum)]
 enum CoordinateSystem {
+    #[value(name = "D96TM")]
     D96TM,
+    #[value(name = "D48GK")]
     D48GK,
 }
 
@@ -130,23 +148,23 @@ impl FromStr for CoordinateSystem {
     }
 }
 
-#[derive(Debug)]
-struct Coordinate<'a> {
+#[derive(Debug, Clone)]
+struct Coordinate {
     x: u64,
     y: u64,
-    system: Option<&'a CoordinateSystem>,
-    point_format: Option<&'a PointFormat>,
+    system: Option<CoordinateSystem>,
+    point_format: Option<PointFormat>,
 }
 
-impl<'a> fmt::Display for Coordinate<'a> {
+impl fmt::Display for Coordinate {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if let (Some(system), Some(point_format)) = (self.system, self.point_format) {
+        if let (Some(system), Some(point_format)) = (&self.system, &self.point_format) {
             let coordinate_system = match system {
                 CoordinateSystem::D96TM => "TM",
                 CoordinateSystem::D48GK => "GK",
             };
 
-            let format = match *point_format {
+            let format = match point_format {
                 PointFormat::GKOT => "",
                 PointFormat::OTR => "R",
                 PointFormat::DTM => "1",
@@ -159,7 +177,7 @@ impl<'a> fmt::Display for Coordinate<'a> {
     }
 }
 
-impl<'a> FromStr for Coordinate<'a> {
+impl FromStr for Coordinate {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -167,14 +185,14 @@ impl<'a> FromStr for Coordinate<'a> {
 
         let x = parts
             .next()
-            .expect("No x coordinate")
+            .ok_or("No x coordinate")?
             .parse::<u64>()
-            .expect("X coordinate is not a number");
+            .map_err(|_| "X coordinate is not a number".to_string())?;
         let y = parts
             .next()
-            .expect("No y coordinate")
+            .ok_or("No y coordinate")?
             .parse::<u64>()
-            .expect("Y coordinate is not a number");
+            .map_err(|_| "Y coordinate is not a number".to_string())?;
         Ok(Coordinate {
             x,
             y,
@@ -186,13 +204,72 @@ impl<'a> FromStr for Coordinate<'a> {
 
 #[derive(Parser, Debug)]
 #[clap(author = "Luka Pršina", version = "0.1.0", about = None, long_about = None)]
-struct Args<'a> {
-    /// GKOT, OTR or DTM
+struct Cli {
+    /// Path to a TOML config file with default download flags, overriding
+    /// the platform default config location
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Download LiDAR tiles for a coordinate range or bounding box
+    Download(DownloadArgs),
+    /// Validate downloaded tiles and optionally re-fetch corrupted ones
+    Verify(VerifyArgs),
+    /// Merge downloaded ASC tiles into a single mosaic raster
+    Mosaic(MosaicArgs),
+    /// Generate a shell completion script
+    Completions(CompletionsArgs),
+}
+
+#[derive(Args, Debug)]
+struct DownloadArgs {
+    /// GKOT, OTR or DTM, falls back to the config file
+    #[clap(short, long, ignore_case = true)]
+    point_format: Option<PointFormat>,
+
+    /// ZLAS, LAZ or ASC, falls back to the config file
+    #[clap(short, long, ignore_case = true)]
+    file_format: Option<FileFormat>,
+
+    /// example: b14, falls back to the config file
     #[clap(short, long)]
+    area_code: Option<AreaCode>,
+
+    /// D96TM or D48GK, falls back to the config file, then D96TM
+    #[clap(short = 's', long, ignore_case = true)]
+    coordinate_system: Option<CoordinateSystem>,
+
+    /// first coordinate x_y, required unless --bbox is given
+    #[clap(short = '1', long)]
+    first_coord: Option<Coordinate>,
+
+    /// second coordinate x_y, required unless --bbox is given
+    #[clap(short = '2', long)]
+    second_coord: Option<Coordinate>,
+
+    /// WGS84 bounding box "minlat,minlon,maxlat,maxlon", derives the tile
+    /// range instead of --first-coord/--second-coord (D96TM only)
+    #[clap(short = 'b', long)]
+    bbox: Option<BoundingBox>,
+
+    /// number of concurrent downloads, falls back to the config file, then 2
+    #[clap(long)]
+    concurrency: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+struct VerifyArgs {
+    /// GKOT, OTR or DTM
+    #[clap(short, long, ignore_case = true)]
     point_format: PointFormat,
 
     /// ZLAS, LAZ or ASC
-    #[clap(short, long)]
+    #[clap(short, long, ignore_case = true)]
     file_format: FileFormat,
 
     /// example: b14
@@ -200,19 +277,45 @@ struct Args<'a> {
     area_code: AreaCode,
 
     /// D96TM or D48GK
-    #[clap(short = 's', long, default_value = "D96TM")]
+    #[clap(short = 's', long, default_value = "D96TM", ignore_case = true)]
+    coordinate_system: CoordinateSystem,
+
+    /// Re-download tiles that fail validation
+    #[clap(long)]
+    repair: bool,
+}
+
+#[derive(Args, Debug)]
+struct MosaicArgs {
+    /// GKOT, OTR or DTM - selects which local tiles to merge
+    #[clap(short, long, ignore_case = true)]
+    point_format: PointFormat,
+
+    /// D96TM or D48GK, tagged onto the mosaic output
+    #[clap(short = 's', long, default_value = "D96TM", ignore_case = true)]
     coordinate_system: CoordinateSystem,
 
     /// first coordinate x_y
     #[clap(short = '1', long)]
-    first_coord: Coordinate<'a>,
+    first_coord: Coordinate,
 
     /// second coordinate x_y
     #[clap(short = '2', long)]
-    second_coord: Coordinate<'a>,
+    second_coord: Coordinate,
+
+    /// output .asc file path
+    #[clap(short, long, default_value = "mosaic.asc")]
+    out: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct CompletionsArgs {
+    /// bash, zsh, fish, powershell or elvish
+    shell: clap_complete::Shell,
 }
 
 const CONCURRENT_REQUESTS: usize = 2;
+const OUTPUT_DIR: &str = "output";
 
 struct Link<'a> {
     url: String,
@@ -228,7 +331,7 @@ struct Link<'a> {
     #[allow(dead_code)]
     coordinate_system: &'a CoordinateSystem,
 
-    coordinate: Coordinate<'a>,
+    coordinate: Coordinate,
 }
 
 impl<'a> Link<'a> {
@@ -237,7 +340,7 @@ impl<'a> Link<'a> {
         file_format: &'a FileFormat,
         area_code: &'a AreaCode,
         coordinate_system: &'a CoordinateSystem,
-        coordinate: Coordinate<'a>,
+        coordinate: Coordinate,
     ) -> Self {
         Link {
             url: format!(
@@ -254,32 +357,122 @@ impl<'a> Link<'a> {
 }
 
 // http://gis.arso.gov.si/lidar/gkot/b_14/D96TM/TM_510_74.zlas
-// cargo run -- -p gkot -c 510_74 -f zlas -a b14
+// cargo run -- download -p gkot -c 510_74 -f zlas -a b14
 #[tokio::main]
-async fn main() {
-    let output = Path::new("output");
-    fs::create_dir_all(output).expect("Failed to create output directory");
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let output = Path::new(OUTPUT_DIR);
+    fs::create_dir_all(output).context("Failed to create output directory")?;
+
+    let config = match cli.config.or_else(config::default_config_path) {
+        Some(path) => config::load(&path)?,
+        None => Config::default(),
+    };
+
+    match cli.command {
+        Command::Download(args) => run_download(args, output, &config).await,
+        Command::Verify(args) => verify::run(args, output).await,
+        Command::Mosaic(args) => mosaic::run(args, output),
+        Command::Completions(args) => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(args.shell, &mut command, name, &mut std::io::stdout());
+            Ok(())
+        }
+    }
+}
+
+async fn run_download(
+    mut args: DownloadArgs,
+    output: &Path,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let point_format = match args.point_format.take() {
+        Some(value) => value,
+        None => config
+            .point_format()?
+            .context("--point-format is required (pass it or set it in the config file)")?,
+    };
+    let file_format = match args.file_format.take() {
+        Some(value) => value,
+        None => config
+            .file_format()?
+            .context("--file-format is required (pass it or set it in the config file)")?,
+    };
+    let area_code = match args.area_code.take() {
+        Some(value) => value,
+        None => config
+            .area_code()?
+            .context("--area-code is required (pass it or set it in the config file)")?,
+    };
+    let coordinate_system = match args.coordinate_system.take() {
+        Some(value) => value,
+        None => config
+            .coordinate_system()?
+            .unwrap_or(CoordinateSystem::D96TM),
+    };
+    let concurrency = args
+        .concurrency
+        .take()
+        .or(config.concurrency)
+        .unwrap_or(CONCURRENT_REQUESTS);
+    if concurrency == 0 {
+        anyhow::bail!("--concurrency must be at least 1");
+    }
+
+    let (mut first_coord, mut second_coord) =
+        match (args.bbox, args.first_coord.take(), args.second_coord.take()) {
+            (Some(bbox), _, _) => {
+                if !matches!(coordinate_system, CoordinateSystem::D96TM) {
+                    anyhow::bail!(
+                        "--bbox is currently only supported with --coordinate-system D96TM \
+                         (D48GK projection is not implemented)"
+                    );
+                }
 
-    let mut args = Args::parse();
-    args.first_coord.system = Some(&args.coordinate_system);
-    args.second_coord.system = Some(&args.coordinate_system);
+                let ((min_x, min_y), (max_x, max_y)) = projection::bbox_to_tile_range(bbox);
+                (
+                    Coordinate {
+                        x: min_x,
+                        y: min_y,
+                        system: None,
+                        point_format: None,
+                    },
+                    Coordinate {
+                        x: max_x,
+                        y: max_y,
+                        system: None,
+                        point_format: None,
+                    },
+                )
+            }
+            (None, Some(first), Some(second)) => (first, second),
+            (None, _, _) => {
+                anyhow::bail!(
+                    "Either --bbox or both --first-coord and --second-coord must be given"
+                )
+            }
+        };
+
+    first_coord.system = Some(coordinate_system.clone());
+    second_coord.system = Some(coordinate_system.clone());
 
     let client = Client::new();
 
     let mut links = vec![];
 
-    for x in args.first_coord.x..=args.second_coord.x {
-        for y in args.first_coord.y..=args.second_coord.y {
+    for x in first_coord.x..=second_coord.x {
+        for y in first_coord.y..=second_coord.y {
             let link = Link::new(
-                &args.point_format,
-                &args.file_format,
-                &args.area_code,
-                &args.coordinate_system,
+                &point_format,
+                &file_format,
+                &area_code,
+                &coordinate_system,
                 Coordinate {
                     x,
                     y,
-                    system: Some(&args.coordinate_system),
-                    point_format: Some(&args.point_format),
+                    system: Some(coordinate_system.clone()),
+                    point_format: Some(point_format.clone()),
                 },
             );
 
@@ -290,39 +483,50 @@ async fn main() {
 
     let client = &client;
 
-    let bodies = stream::iter(&links)
+    let progress = ProgressBar::new(links.len() as u64);
+    progress.set_style(
+        ProgressStyle::with_template("{bar:40.cyan/blue} {pos}/{len} tiles | {msg}")
+            .expect("Invalid progress bar template")
+            .progress_chars("=> "),
+    );
+
+    let mut summary = download::DownloadSummary::default();
+    let mut last_tick = std::time::Instant::now();
+    let mut last_bytes = 0u64;
+
+    stream::iter(&links)
         .map(|link| {
             let client = client.clone();
-            async move {
-                let response = client
-                    .get(&link.url)
-                    .send()
-                    .await
-                    .expect(&format!("Failed to get file from link {}", &link.url));
-                response.bytes().await
+            async move { download::download_link(&client, link, output).await }
+        })
+        .buffer_unordered(concurrency)
+        .for_each(|outcome| {
+            if let download::DownloadOutcome::Failed(error) = &outcome {
+                progress.println(format!("Download failed: {:#}", error));
             }
+            summary.record(&outcome);
+            progress.inc(1);
+
+            let now = std::time::Instant::now();
+            let elapsed = now.duration_since(last_tick).as_secs_f64();
+            let rate = if elapsed > 0.0 {
+                ((summary.bytes - last_bytes) as f64 / elapsed) as u64
+            } else {
+                0
+            };
+            last_tick = now;
+            last_bytes = summary.bytes;
+
+            progress.set_message(format!(
+                "{} downloaded, {}/s",
+                download::format_bytes(summary.bytes),
+                download::format_bytes(rate)
+            ));
+            future::ready(())
         })
-        .buffer_unordered(CONCURRENT_REQUESTS);
-
-    {
-        let links = &links;
-
-        bodies
-            .enumerate()
-            .for_each(|(pos, body)| async move {
-                let link = &links[pos];
+        .await;
 
-                let path = output.join(format!(
-                    "{}_{}.{}",
-                    &link.coordinate.x, &link.coordinate.y, &link.point_format
-                ));
-                let mut file = File::create(&path).expect("Failed to create file");
+    progress.finish_with_message(format!("{}", summary));
 
-                match &body {
-                    Ok(b) => file.write_all(&b).expect("Failed to write bytes"),
-                    Err(e) => eprintln!("Got an error: {}", e),
-                }
-            })
-            .await;
-    }
+    Ok(())
 }
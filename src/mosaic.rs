@@ -0,0 +1,216 @@
+use crate::MosaicArgs;
+use anyhow::{bail, Context, Result};
+use std::{collections::HashMap, fs, path::Path};
+
+/// The subset of an Esri ASCII grid header that must agree across every
+/// tile being merged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AscHeader {
+    ncols: usize,
+    nrows: usize,
+    cellsize: f64,
+    nodata_value: f64,
+}
+
+struct AscTile {
+    x: u64,
+    y: u64,
+    header: AscHeader,
+    rows: Vec<String>,
+}
+
+/// Reads a downloaded ASC tile's 6-line Esri ASCII grid header and its data
+/// rows. `xllcorner`/`yllcorner` are read but not trusted for placement -
+/// placement instead comes from the tile's own grid `x`/`y`, same as the
+/// rest of this tool.
+fn parse_asc_tile(path: &Path, x: u64, y: u64) -> Result<AscTile> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut lines = contents.lines();
+
+    let mut ncols = None;
+    let mut nrows = None;
+    let mut cellsize = None;
+    let mut nodata_value = None;
+
+    for _ in 0..6 {
+        let line = lines
+            .next()
+            .with_context(|| format!("{} is missing its ASC header", path.display()))?;
+        let mut fields = line.split_whitespace();
+        let key = fields.next().unwrap_or_default().to_lowercase();
+        let value = fields.next().unwrap_or_default();
+
+        match key.as_str() {
+            "ncols" => ncols = Some(value.parse::<usize>().context("Invalid ncols")?),
+            "nrows" => nrows = Some(value.parse::<usize>().context("Invalid nrows")?),
+            "cellsize" => cellsize = Some(value.parse::<f64>().context("Invalid cellsize")?),
+            "nodata_value" => {
+                nodata_value = Some(value.parse::<f64>().context("Invalid NODATA_value")?)
+            }
+            "xllcorner" | "xllcenter" | "yllcorner" | "yllcenter" => {}
+            other => bail!(
+                "{}: unexpected ASC header field '{}'",
+                path.display(),
+                other
+            ),
+        }
+    }
+
+    let header = AscHeader {
+        ncols: ncols.with_context(|| format!("{} is missing ncols", path.display()))?,
+        nrows: nrows.with_context(|| format!("{} is missing nrows", path.display()))?,
+        cellsize: cellsize.with_context(|| format!("{} is missing cellsize", path.display()))?,
+        nodata_value: nodata_value
+            .with_context(|| format!("{} is missing NODATA_value", path.display()))?,
+    };
+
+    let rows: Vec<String> = lines.map(str::to_string).collect();
+    if rows.len() != header.nrows {
+        bail!(
+            "{}: header declares {} rows but file has {}",
+            path.display(),
+            header.nrows,
+            rows.len()
+        );
+    }
+
+    Ok(AscTile { x, y, header, rows })
+}
+
+/// Merges every downloaded ASC tile in `args.first_coord..=args.second_coord`
+/// into a single Esri ASCII grid, tagged with a sidecar `.prj`-style file
+/// recording the chosen `CoordinateSystem`.
+pub fn run(args: MosaicArgs, output: &Path) -> Result<()> {
+    let min_x = args.first_coord.x.min(args.second_coord.x);
+    let max_x = args.first_coord.x.max(args.second_coord.x);
+    let min_y = args.first_coord.y.min(args.second_coord.y);
+    let max_y = args.first_coord.y.max(args.second_coord.y);
+
+    let mut tiles = vec![];
+    for x in min_x..=max_x {
+        for y in min_y..=max_y {
+            let path = output.join(format!("{}_{}.{}", x, y, args.point_format));
+            if path.exists() {
+                tiles.push(parse_asc_tile(&path, x, y)?);
+            }
+        }
+    }
+
+    if tiles.is_empty() {
+        bail!(
+            "No {} tiles found for the requested rectangle in {}",
+            args.point_format,
+            output.display()
+        );
+    }
+
+    let header = tiles[0].header;
+    for tile in &tiles {
+        if tile.header != header {
+            bail!(
+                "{}_{}.{}: ASC header does not match the rest of the mosaic",
+                tile.x,
+                tile.y,
+                args.point_format
+            );
+        }
+    }
+
+    let by_coord: HashMap<(u64, u64), &AscTile> =
+        tiles.iter().map(|tile| ((tile.x, tile.y), tile)).collect();
+
+    let nodata_row = vec![header.nodata_value.to_string(); header.ncols].join(" ");
+
+    let mut rows = Vec::with_capacity((max_y - min_y + 1) as usize * header.nrows);
+    for y in (min_y..=max_y).rev() {
+        for local_row in 0..header.nrows {
+            let mut row = Vec::with_capacity((max_x - min_x + 1) as usize);
+            for x in min_x..=max_x {
+                match by_coord.get(&(x, y)) {
+                    Some(tile) => row.push(tile.rows[local_row].as_str()),
+                    None => row.push(nodata_row.as_str()),
+                }
+            }
+            rows.push(row.join(" "));
+        }
+    }
+
+    let total_cols = (max_x - min_x + 1) * header.ncols as u64;
+    let total_rows = (max_y - min_y + 1) * header.nrows as u64;
+
+    let mut contents = format!(
+        "ncols {}\nnrows {}\nxllcorner {}\nyllcorner {}\ncellsize {}\nNODATA_value {}\n",
+        total_cols,
+        total_rows,
+        min_x * 1000,
+        min_y * 1000,
+        header.cellsize,
+        header.nodata_value
+    );
+    contents.push_str(&rows.join("\n"));
+    contents.push('\n');
+
+    fs::write(&args.out, contents)
+        .with_context(|| format!("Failed to write {}", args.out.display()))?;
+
+    let prj_path = args.out.with_extension("prj");
+    fs::write(&prj_path, format!("{}\n", args.coordinate_system))
+        .with_context(|| format!("Failed to write {}", prj_path.display()))?;
+
+    println!(
+        "Wrote {} ({} x {} cells) and {}",
+        args.out.display(),
+        total_cols,
+        total_rows,
+        prj_path.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coordinate, CoordinateSystem, PointFormat};
+
+    #[test]
+    fn stitches_tiles_with_the_northernmost_row_first() {
+        let dir =
+            std::env::temp_dir().join(format!("gis_lidar_mosaic_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let header = "ncols 1\nnrows 1\nxllcorner 0\nyllcorner 0\ncellsize 1\nNODATA_value -9999\n";
+        fs::write(dir.join("0_0.gkot"), format!("{}1\n", header)).unwrap();
+        fs::write(dir.join("0_1.gkot"), format!("{}2\n", header)).unwrap();
+
+        let out = dir.join("mosaic.asc");
+        let args = MosaicArgs {
+            point_format: PointFormat::GKOT,
+            coordinate_system: CoordinateSystem::D96TM,
+            first_coord: Coordinate {
+                x: 0,
+                y: 0,
+                system: None,
+                point_format: None,
+            },
+            second_coord: Coordinate {
+                x: 0,
+                y: 1,
+                system: None,
+                point_format: None,
+            },
+            out: out.clone(),
+        };
+
+        run(args, &dir).unwrap();
+
+        let contents = fs::read_to_string(&out).unwrap();
+        let rows: Vec<&str> = contents.lines().skip(6).collect();
+        // Tile y=1 lies north of tile y=0, so its row must come first -
+        // a flipped row order would silently mirror every mosaic top-to-bottom.
+        assert_eq!(rows, vec!["2", "1"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
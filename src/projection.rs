@@ -0,0 +1,243 @@
+use std::str::FromStr;
+
+/// Semi-major axis of the GRS80 ellipsoid underlying D96/TM (EPSG:3794).
+const GRS80_A: f64 = 6_378_137.0;
+/// Flattening of the GRS80 ellipsoid.
+const GRS80_F: f64 = 1.0 / 298.257_222_101;
+/// Central meridian of the D96/TM projection.
+const D96TM_LAMBDA0_DEG: f64 = 15.0;
+/// Scale factor on the central meridian.
+const D96TM_K0: f64 = 0.9999;
+const D96TM_FALSE_EASTING: f64 = 500_000.0;
+const D96TM_FALSE_NORTHING: f64 = -5_000_000.0;
+
+/// Rough WGS84 extent of Slovenia, used to sanity-check a `--bbox` before
+/// it is projected into the ARSO tile grid.
+const SLOVENIA_LAT: std::ops::RangeInclusive<f64> = 45.0..=47.0;
+const SLOVENIA_LON: std::ops::RangeInclusive<f64> = 13.0..=17.0;
+
+/// A validated WGS84 latitude/longitude pair.
+#[derive(Debug, Clone, Copy)]
+pub struct LatLon {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl LatLon {
+    pub fn new(lat: f64, lon: f64) -> Result<Self, String> {
+        if !(-90.0..=90.0).contains(&lat) {
+            return Err(format!("Latitude {} is out of range [-90, 90]", lat));
+        }
+        if !(-180.0..=180.0).contains(&lon) {
+            return Err(format!("Longitude {} is out of range [-180, 180]", lon));
+        }
+        Ok(LatLon { lat, lon })
+    }
+
+    fn in_slovenia(&self) -> bool {
+        SLOVENIA_LAT.contains(&self.lat) && SLOVENIA_LON.contains(&self.lon)
+    }
+}
+
+impl FromStr for LatLon {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (lat, lon) = s
+            .split_once(',')
+            .ok_or_else(|| format!("Expected \"lat,lon\", got \"{}\"", s))?;
+        let lat = lat.trim().parse::<f64>().map_err(|e| e.to_string())?;
+        let lon = lon.trim().parse::<f64>().map_err(|e| e.to_string())?;
+        LatLon::new(lat, lon)
+    }
+}
+
+/// A WGS84 bounding box given as its south-west and north-east corners.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingBox {
+    pub min: LatLon,
+    pub max: LatLon,
+}
+
+impl FromStr for BoundingBox {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(',').collect();
+        let [min_lat, min_lon, max_lat, max_lon]: [&str; 4] = parts
+            .try_into()
+            .map_err(|_| format!("Expected \"minlat,minlon,maxlat,maxlon\", got \"{}\"", s))?;
+
+        let min = LatLon::new(
+            min_lat
+                .trim()
+                .parse()
+                .map_err(|_| "Invalid minlat".to_string())?,
+            min_lon
+                .trim()
+                .parse()
+                .map_err(|_| "Invalid minlon".to_string())?,
+        )?;
+        let max = LatLon::new(
+            max_lat
+                .trim()
+                .parse()
+                .map_err(|_| "Invalid maxlat".to_string())?,
+            max_lon
+                .trim()
+                .parse()
+                .map_err(|_| "Invalid maxlon".to_string())?,
+        )?;
+
+        if min.lat > max.lat || min.lon > max.lon {
+            return Err("Bounding box minimum must not exceed its maximum".to_string());
+        }
+        if !min.in_slovenia() || !max.in_slovenia() {
+            return Err("Bounding box corners must fall within Slovenia's extent".to_string());
+        }
+
+        Ok(BoundingBox { min, max })
+    }
+}
+
+/// Forward Transverse Mercator projection of a WGS84 point into D96/TM
+/// (EPSG:3794) easting/northing metres, on the GRS80 ellipsoid.
+fn project_to_d96tm(point: LatLon) -> (f64, f64) {
+    let a = GRS80_A;
+    let f = GRS80_F;
+    let e2 = f * (2.0 - f);
+    let ep2 = e2 / (1.0 - e2);
+
+    let phi = point.lat.to_radians();
+    let lambda = point.lon.to_radians();
+    let lambda0 = D96TM_LAMBDA0_DEG.to_radians();
+
+    let m = a
+        * ((1.0 - e2 / 4.0 - 3.0 * e2.powi(2) / 64.0 - 5.0 * e2.powi(3) / 256.0) * phi
+            - (3.0 * e2 / 8.0 + 3.0 * e2.powi(2) / 32.0 + 45.0 * e2.powi(3) / 1024.0)
+                * (2.0 * phi).sin()
+            + (15.0 * e2.powi(2) / 256.0 + 45.0 * e2.powi(3) / 1024.0) * (4.0 * phi).sin()
+            - (35.0 * e2.powi(3) / 3072.0) * (6.0 * phi).sin());
+
+    let sin_phi = phi.sin();
+    let cos_phi = phi.cos();
+    let tan_phi = phi.tan();
+
+    let n = a / (1.0 - e2 * sin_phi.powi(2)).sqrt();
+    let t = tan_phi.powi(2);
+    let c = ep2 * cos_phi.powi(2);
+    let aa = (lambda - lambda0) * cos_phi;
+
+    let easting = D96TM_FALSE_EASTING
+        + D96TM_K0
+            * n
+            * (aa
+                + (1.0 - t + c) * aa.powi(3) / 6.0
+                + (5.0 - 18.0 * t + t.powi(2) + 72.0 * c - 58.0 * ep2) * aa.powi(5) / 120.0);
+
+    let northing = D96TM_FALSE_NORTHING
+        + D96TM_K0
+            * (m + n
+                * tan_phi
+                * (aa.powi(2) / 2.0
+                    + (5.0 - t + 9.0 * c + 4.0 * c.powi(2)) * aa.powi(4) / 24.0
+                    + (61.0 - 58.0 * t + t.powi(2) + 600.0 * c - 330.0 * ep2) * aa.powi(6)
+                        / 720.0));
+
+    (easting, northing)
+}
+
+/// The inclusive ARSO 1 km tile grid range `(min_x, min_y)..=(max_x, max_y)`
+/// covering a WGS84 bounding box on the D96/TM grid.
+pub fn bbox_to_tile_range(bbox: BoundingBox) -> ((u64, u64), (u64, u64)) {
+    let corners = [
+        LatLon {
+            lat: bbox.min.lat,
+            lon: bbox.min.lon,
+        },
+        LatLon {
+            lat: bbox.min.lat,
+            lon: bbox.max.lon,
+        },
+        LatLon {
+            lat: bbox.max.lat,
+            lon: bbox.min.lon,
+        },
+        LatLon {
+            lat: bbox.max.lat,
+            lon: bbox.max.lon,
+        },
+    ];
+
+    let tiles: Vec<(u64, u64)> = corners
+        .iter()
+        .map(|&corner| {
+            let (easting, northing) = project_to_d96tm(corner);
+            (
+                (easting / 1000.0).floor() as u64,
+                (northing / 1000.0).floor() as u64,
+            )
+        })
+        .collect();
+
+    let min_x = tiles.iter().map(|(x, _)| *x).min().expect("4 corners");
+    let max_x = tiles.iter().map(|(x, _)| *x).max().expect("4 corners");
+    let min_y = tiles.iter().map(|(_, y)| *y).min().expect("4 corners");
+    let max_y = tiles.iter().map(|(_, y)| *y).max().expect("4 corners");
+
+    ((min_x, min_y), (max_x, max_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn central_meridian_projects_to_false_easting() {
+        // On the central meridian the delta-longitude term is zero, so the
+        // forward TM series collapses exactly to the false easting regardless
+        // of latitude. Pins the formula's sign/term structure against a
+        // silent axis inversion or dropped series term.
+        for lat in [45.2, 46.0, 46.8] {
+            let point = LatLon::new(lat, D96TM_LAMBDA0_DEG).unwrap();
+            let (easting, _) = project_to_d96tm(point);
+            assert!(
+                (easting - D96TM_FALSE_EASTING).abs() < 1e-6,
+                "easting at the central meridian should equal the false easting, got {}",
+                easting
+            );
+        }
+    }
+
+    #[test]
+    fn easting_increases_east_of_central_meridian() {
+        let west = project_to_d96tm(LatLon::new(46.0, 14.5).unwrap());
+        let east = project_to_d96tm(LatLon::new(46.0, 15.5).unwrap());
+        assert!(east.0 > west.0, "moving east should increase easting");
+    }
+
+    #[test]
+    fn northing_increases_with_latitude() {
+        let south = project_to_d96tm(LatLon::new(45.2, 15.0).unwrap());
+        let north = project_to_d96tm(LatLon::new(46.8, 15.0).unwrap());
+        assert!(north.1 > south.1, "moving north should increase northing");
+    }
+
+    #[test]
+    fn bbox_to_tile_range_matches_direct_projection_of_a_single_point() {
+        let point = LatLon::new(46.0, 15.0).unwrap();
+        let bbox = BoundingBox {
+            min: point,
+            max: point,
+        };
+        let (easting, northing) = project_to_d96tm(point);
+        let expected = (
+            (easting / 1000.0).floor() as u64,
+            (northing / 1000.0).floor() as u64,
+        );
+
+        let (min, max) = bbox_to_tile_range(bbox);
+        assert_eq!(min, expected);
+        assert_eq!(max, expected);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{download, Coordinate, FileFormat, Link, PointFormat, VerifyArgs};
+use anyhow::{Context, Result};
+use reqwest::Client;
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// The result of validating a single tile file's header against its
+/// expected [`FileFormat`].
+enum TileStatus {
+    Valid,
+    Invalid(String),
+}
+
+/// A tile file found under the output directory, with its parsed x/y grid
+/// coordinate so a [`Link`] can be reconstructed for `--repair`.
+struct Tile {
+    path: PathBuf,
+    x: u64,
+    y: u64,
+}
+
+/// Walks `output`, validates every tile matching `file_format`, and with
+/// `args.repair` re-downloads whatever fails validation.
+pub async fn run(args: VerifyArgs, output: &Path) -> Result<()> {
+    let tiles = discover_tiles(output, &args.point_format)?;
+
+    if tiles.is_empty() {
+        println!(
+            "No {} tiles found in {}",
+            args.file_format,
+            output.display()
+        );
+        return Ok(());
+    }
+
+    let client = Client::new();
+    let mut valid = 0;
+    let mut invalid = 0;
+    let mut repaired = 0;
+
+    for tile in tiles {
+        match validate_tile(&tile.path, &args.file_format)? {
+            TileStatus::Valid => valid += 1,
+            TileStatus::Invalid(reason) => {
+                invalid += 1;
+                eprintln!("{}: {}", tile.path.display(), reason);
+
+                if args.repair && repair_tile(&client, &tile, &args, output).await? {
+                    repaired += 1;
+                    println!("Repaired {}", tile.path.display());
+                }
+            }
+        }
+    }
+
+    print!("{} valid, {} invalid", valid, invalid);
+    if args.repair {
+        println!(", {} repaired", repaired);
+    } else {
+        println!();
+    }
+
+    Ok(())
+}
+
+// Tiles are saved locally as "{x}_{y}.{point_format}" (see `download::dest_path`),
+// so tiles are discovered by the point format's extension, not the file format.
+fn discover_tiles(output: &Path, point_format: &PointFormat) -> Result<Vec<Tile>> {
+    let suffix = format!(".{}", point_format);
+    let mut tiles = vec![];
+
+    for entry in
+        fs::read_dir(output).with_context(|| format!("Failed to read {}", output.display()))?
+    {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+
+        if let Some(stem) = file_name.strip_suffix(&suffix) {
+            if let Some((x, y)) = stem.split_once('_') {
+                if let (Ok(x), Ok(y)) = (x.parse(), y.parse()) {
+                    tiles.push(Tile {
+                        path: entry.path(),
+                        x,
+                        y,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(tiles)
+}
+
+/// Sniffs a tile's header/magic to catch truncated downloads or the HTML
+/// error pages the ARSO server sometimes returns instead of tile data.
+fn validate_tile(path: &Path, file_format: &FileFormat) -> Result<TileStatus> {
+    let mut header = vec![0u8; 256];
+    let mut file =
+        fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let read = file.read(&mut header)?;
+    header.truncate(read);
+    let file_size = file.metadata()?.len();
+
+    if header.starts_with(b"<!DOCTYPE") || header.starts_with(b"<html") {
+        return Ok(TileStatus::Invalid(
+            "File looks like an HTML error page, not tile data".to_string(),
+        ));
+    }
+
+    match file_format {
+        // LAZ and ZLAS are both compressed LAS variants and share the same
+        // LAS header layout, so they get the same magic/point-count/size check.
+        FileFormat::LAZ | FileFormat::ZLAS => {
+            if !header.starts_with(b"LASF") {
+                return Ok(TileStatus::Invalid("Missing LASF signature".to_string()));
+            }
+
+            let point_count = header
+                .get(107..111)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(u32::from_le_bytes)
+                .unwrap_or(0);
+            if point_count == 0 {
+                return Ok(TileStatus::Invalid(
+                    "LAS header reports zero point records".to_string(),
+                ));
+            }
+
+            let offset_to_point_data = header
+                .get(96..100)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(u32::from_le_bytes)
+                .unwrap_or(0);
+            if file_size < offset_to_point_data as u64 {
+                return Ok(TileStatus::Invalid(format!(
+                    "File is truncated: {} bytes but header declares point data starting at offset {}",
+                    file_size, offset_to_point_data
+                )));
+            }
+        }
+        FileFormat::ASC => {
+            let text = String::from_utf8_lossy(&header).to_lowercase();
+            for expected in ["ncols", "nrows", "cellsize"] {
+                if !text.contains(expected) {
+                    return Ok(TileStatus::Invalid(format!(
+                        "ASC header is missing the '{}' field",
+                        expected
+                    )));
+                }
+            }
+        }
+    }
+
+    Ok(TileStatus::Valid)
+}
+
+async fn repair_tile(
+    client: &Client,
+    tile: &Tile,
+    args: &VerifyArgs,
+    output: &Path,
+) -> Result<bool> {
+    let coordinate = Coordinate {
+        x: tile.x,
+        y: tile.y,
+        system: Some(args.coordinate_system.clone()),
+        point_format: Some(args.point_format.clone()),
+    };
+    let link = Link::new(
+        &args.point_format,
+        &args.file_format,
+        &args.area_code,
+        &args.coordinate_system,
+        coordinate,
+    );
+
+    fs::remove_file(&tile.path)
+        .with_context(|| format!("Failed to remove {}", tile.path.display()))?;
+
+    match download::download_link(client, &link, output).await {
+        download::DownloadOutcome::Succeeded(_) | download::DownloadOutcome::Skipped => Ok(true),
+        download::DownloadOutcome::Failed(error) => {
+            eprintln!("Failed to repair {}: {:#}", tile.path.display(), error);
+            Ok(false)
+        }
+    }
+}